@@ -0,0 +1,111 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
+use near_sdk::{AccountId, Balance, Timestamp};
+
+use crate::types::U256;
+
+/// Scales `reward_per_share` so integer division in [`Farm::distribute`]
+/// doesn't lose all its precision for farms with a small token amount.
+pub const FARM_PRECISION: u128 = 1_000_000_000_000;
+
+/// A time-linear reward farm: `amount` of `token_id` is handed out evenly
+/// between `start_date` and `end_date`, split among stakers proportional to
+/// their stake shares at the time the reward accrues.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+pub struct Farm {
+    pub name: String,
+    pub token_id: AccountId,
+    pub amount: Balance,
+    pub start_date: Timestamp,
+    pub end_date: Timestamp,
+    /// Timestamp up to which `amount` has already been folded into
+    /// `reward_per_share`.
+    pub last_distribution: Timestamp,
+    /// Accumulated `reward * FARM_PRECISION / total_stake_shares`, growing
+    /// monotonically as time passes between `start_date` and `end_date`.
+    /// Kept as a plain `u128`: the `U256` widening only happens transiently
+    /// inside [`Self::distribute`] and [`Self::unclaimed_reward`] to avoid
+    /// overflow during multiplication.
+    pub reward_per_share: Balance,
+    /// When set, claims from this farm don't pay out instantly -- they're
+    /// routed into a per-account `VestingSchedule` that unlocks linearly,
+    /// period by period, between `cliff` and `end`.
+    pub vesting: Option<VestingTerms>,
+}
+
+/// The `{cliff_date, vesting_end_date, period_count}` a farm can optionally
+/// be deployed with, following the serum/lockup cliff + linear-periods
+/// model. Claimed rewards aren't liquid until `withdraw_vested` releases
+/// them period by period.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug)]
+pub struct VestingTerms {
+    pub cliff: Timestamp,
+    pub end: Timestamp,
+    pub periods: u32,
+}
+
+impl Farm {
+    pub fn new(
+        name: String,
+        token_id: AccountId,
+        amount: Balance,
+        start_date: Timestamp,
+        end_date: Timestamp,
+        vesting: Option<VestingTerms>,
+    ) -> Self {
+        assert!(end_date > start_date, "end_date must be after start_date");
+        if let Some(vesting) = &vesting {
+            assert!(vesting.end > vesting.cliff, "vesting_end_date must be after cliff_date");
+            assert!(vesting.periods > 0, "period_count must be positive");
+        }
+        Self {
+            name,
+            token_id,
+            amount,
+            start_date,
+            end_date,
+            last_distribution: start_date,
+            reward_per_share: 0,
+            vesting,
+        }
+    }
+
+    /// Folds whatever reward has accrued since `last_distribution` into
+    /// `reward_per_share`, given the current timestamp and total stake
+    /// shares outstanding. No-ops before `start_date` or once fully
+    /// distributed.
+    pub fn distribute(&mut self, now: Timestamp, total_stake_shares: Balance) {
+        if total_stake_shares == 0 || now <= self.start_date {
+            return;
+        }
+        let distribution_end = std::cmp::min(now, self.end_date);
+        if distribution_end <= self.last_distribution {
+            return;
+        }
+        let duration = (self.end_date - self.start_date) as u128;
+        let elapsed = (distribution_end - self.last_distribution) as u128;
+        let newly_distributed = U256::from(self.amount) * U256::from(elapsed) / U256::from(duration);
+        let added_rps = newly_distributed * U256::from(FARM_PRECISION) / U256::from(total_stake_shares);
+        self.reward_per_share += added_rps.as_u128();
+        self.last_distribution = distribution_end;
+    }
+
+    pub fn unclaimed_reward(&self, stake_shares: Balance, rps_paid: Balance) -> Balance {
+        if self.reward_per_share <= rps_paid {
+            return 0;
+        }
+        (U256::from(self.reward_per_share - rps_paid) * U256::from(stake_shares) / U256::from(FARM_PRECISION)).as_u128()
+    }
+}
+
+/// JSON-friendly view of a [`Farm`].
+#[derive(near_sdk::serde::Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HumanReadableFarm {
+    pub farm_id: u64,
+    pub name: String,
+    pub token_id: AccountId,
+    pub amount: near_sdk::json_types::U128,
+    pub start_date: U64,
+    pub end_date: U64,
+}
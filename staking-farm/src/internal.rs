@@ -0,0 +1,107 @@
+use near_sdk::{env, AccountId, Balance};
+
+use crate::types::{Account, U256};
+use crate::*;
+
+impl StakingContract {
+    /// Converts the number of "stake" shares to the equivalent NEAR amount
+    /// at the current exchange rate (`total_staked_balance / total_stake_shares`).
+    pub(crate) fn staked_amount_from_num_shares(&self, num_shares: Balance) -> Balance {
+        if self.total_stake_shares == 0 {
+            return 0;
+        }
+        (U256::from(self.total_staked_balance) * U256::from(num_shares)
+            / U256::from(self.total_stake_shares))
+        .as_u128()
+    }
+
+    /// Converts a NEAR amount to the number of "stake" shares it's worth at
+    /// the current exchange rate, rounding up so the pool never over-pays
+    /// when shares are given up (`unstake`).
+    pub(crate) fn num_shares_from_staked_amount_rounded(&self, amount: Balance) -> Balance {
+        if self.total_staked_balance == 0 {
+            return amount;
+        }
+        ((U256::from(self.total_stake_shares) * U256::from(amount)
+            + U256::from(self.total_staked_balance - 1))
+            / U256::from(self.total_staked_balance))
+        .as_u128()
+    }
+
+    pub(crate) fn internal_get_account(&self, account_id: &AccountId) -> Account {
+        self.accounts.get(account_id).unwrap_or_default()
+    }
+
+    pub(crate) fn internal_save_account(&mut self, account_id: &AccountId, account: &Account) {
+        if account.stake_shares == 0 && account.unbonding.is_empty() && account.drop_pending.is_empty() {
+            self.accounts.remove(account_id);
+        } else {
+            self.accounts.insert(account_id, account);
+        }
+    }
+
+    /// Distributes every farm and rolls `account_id`'s settled-but-unclaimed
+    /// reward into `account_farm_pending`, snapping `rps_paid` to the
+    /// now-current `reward_per_share`. Must run before `account_id`'s
+    /// `stake_shares` changes (mint, burn, or either side of an
+    /// `ft_transfer`), same as `internal_settle_drops`: it keeps the share
+    /// count `Farm::unclaimed_reward` is computed against pinned to what the
+    /// account held since its last settlement.
+    pub(crate) fn internal_settle_farms(&mut self, account_id: &AccountId) {
+        let account = self.internal_get_account(account_id);
+        let now = env::block_timestamp();
+        for farm_id in 0..self.farms.len() {
+            let mut farm = self.farms.get(farm_id).unwrap();
+            farm.distribute(now, self.total_stake_shares);
+
+            let key = (account_id.clone(), farm_id);
+            let rps_paid = self.account_farm_rps_paid.get(&key).unwrap_or_default();
+            let reward = farm.unclaimed_reward(account.stake_shares, rps_paid);
+            if reward > 0 {
+                let pending = self.account_farm_pending.get(&key).unwrap_or_default();
+                self.account_farm_pending.insert(&key, &(pending + reward));
+            }
+            self.account_farm_rps_paid.insert(&key, &farm.reward_per_share);
+            self.farms.replace(farm_id, &farm);
+        }
+    }
+
+    /// Mints `num_shares` stake shares (stNEAR) to `account_id`, growing the
+    /// token's total supply. Leaves `total_staked_balance` alone: the caller
+    /// adds the matching NEAR to it so the exchange rate doesn't move just
+    /// because of a deposit.
+    pub(crate) fn internal_mint_shares(&mut self, account_id: &AccountId, num_shares: Balance) {
+        self.internal_settle_drops(account_id);
+        self.internal_settle_farms(account_id);
+
+        let mut account = self.internal_get_account(account_id);
+        account.stake_shares += num_shares;
+        self.internal_save_account(account_id, &account);
+        self.total_stake_shares += num_shares;
+
+        crate::token::emit_ft_mint(account_id, num_shares);
+    }
+
+    /// Burns `num_shares` stake shares (stNEAR) from `account_id`, e.g. when
+    /// unstaking. Panics if the account doesn't hold enough.
+    pub(crate) fn internal_burn_shares(&mut self, account_id: &AccountId, num_shares: Balance) {
+        self.internal_settle_drops(account_id);
+        self.internal_settle_farms(account_id);
+
+        let mut account = self.internal_get_account(account_id);
+        assert!(account.stake_shares >= num_shares, "Not enough stake shares");
+        account.stake_shares -= num_shares;
+        self.internal_save_account(account_id, &account);
+        self.total_stake_shares -= num_shares;
+
+        crate::token::emit_ft_burn(account_id, num_shares);
+    }
+
+    pub(crate) fn assert_owner(&self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Can only be called by the owner"
+        );
+    }
+}
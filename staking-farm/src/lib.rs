@@ -0,0 +1,519 @@
+mod farm;
+mod internal;
+mod owner;
+mod reward_queue;
+mod token;
+mod types;
+mod validators;
+mod views;
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, Vector};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::{
+    env, ext_contract, near_bindgen, AccountId, Balance, EpochHeight, Gas, PanicOnDefault,
+    Promise, PromiseOrValue, PublicKey,
+};
+
+pub use crate::farm::{Farm, HumanReadableFarm};
+pub use crate::types::{Account, Ratio, RewardEvent, UnbondEntry, ValidatorInfo, VestingSchedule};
+
+near_sdk::setup_alloc!();
+
+const GAS_FOR_FARM_PAYOUT: Gas = Gas(20_000_000_000_000);
+
+/// Matches NEAR's own native unstaking lock so a freshly deployed pool with
+/// no owner override behaves like a plain staking pool.
+const DEFAULT_UNBOND_EPOCHS: EpochHeight = 4;
+
+/// NEAR kept out of `rebalance`'s idle-balance calculation as a storage
+/// reserve, so the contract never stakes away the balance it needs to cover
+/// its own state (account records, farms, the reward queue, ...).
+pub(crate) const MIN_BALANCE_FOR_STORAGE: Balance = 5_000_000_000_000_000_000_000_000; // 5 NEAR
+
+/// A NEAR staking pool that also mints a liquid staking token (stNEAR) for
+/// every delegator and runs time-linear reward farms funded by third-party
+/// NEP-141 tokens, in addition to the validator's own staking rewards.
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct StakingContract {
+    pub owner_id: AccountId,
+    pub stake_public_key: PublicKey,
+    /// Total NEAR this pool has staked with the validator, i.e. the
+    /// numerator half of the stNEAR exchange rate.
+    pub total_staked_balance: Balance,
+    /// Total stNEAR ("stake shares") in circulation -- the pool's own
+    /// NEP-141 total supply.
+    pub total_stake_shares: Balance,
+    /// This account's locked plus unlocked balance as of the previous
+    /// `ping` (raised by each deposit), used to detect newly accrued
+    /// staking rewards.
+    pub last_total_balance: Balance,
+    pub accounts: LookupMap<AccountId, Account>,
+    pub paused: bool,
+    pub reward_fee_fraction: Ratio,
+    pub burn_fee_fraction: Ratio,
+    pub farms: Vector<Farm>,
+    pub account_farm_rps_paid: LookupMap<(AccountId, u64), Balance>,
+    /// Farm reward settled by `internal_settle_farms` (see `internal.rs`)
+    /// but not yet paid out by `claim`, keyed by `(account_id, farm_id)`.
+    /// Settling on every `stake_shares` change -- mint, burn, or either side
+    /// of an `ft_transfer` -- keeps the share count `Farm::unclaimed_reward`
+    /// is computed against pinned to what the account held since its last
+    /// settlement, instead of letting a deposit retroactively inflate a
+    /// claim against reward accrued before the account joined.
+    pub account_farm_pending: LookupMap<(AccountId, u64), Balance>,
+    /// Number of epochs an `unstake`d amount must wait before `withdraw` can
+    /// release it. Owner-settable so operators can run a cooldown longer
+    /// than NEAR's native 4-epoch unstaking lock.
+    pub unbond_epochs: EpochHeight,
+    /// Ring buffer of ad-hoc reward drops, keyed by a global index rather
+    /// than a `Vector` so evicting the oldest entry is O(1) (see the
+    /// `reward_queue` module).
+    pub reward_queue: LookupMap<u64, RewardEvent>,
+    /// Global index of the next event `internal_push_reward_event` will
+    /// write to; also doubles as "one past the newest event".
+    pub reward_queue_head: u64,
+    /// Global index of the oldest event still present in `reward_queue`.
+    pub reward_queue_tail: u64,
+    /// Per-account index into the reward queue: the next event that
+    /// account hasn't claimed yet.
+    pub account_drop_cursor: LookupMap<AccountId, u64>,
+    /// Claimed-but-not-yet-liquid rewards for vesting farms, keyed by
+    /// `(account_id, farm_id)`. Only farms deployed with vesting terms ever
+    /// get an entry here; other farms pay out instantly on `claim`.
+    pub account_vesting: LookupMap<(AccountId, u64), VestingSchedule>,
+    /// Weighted set of underlying staking pools this meta-pool delegates
+    /// to. Empty by default, in which case the pool keeps staking directly
+    /// under its own account via `stake_public_key` (see `deposit_and_stake`).
+    pub validators: Vector<ValidatorInfo>,
+    /// Sum of every account's unbonding entries not yet withdrawn. Reserved
+    /// out of `rebalance`'s idle-balance figure so it never stakes away NEAR
+    /// this pool already owes to depositors who are waiting to `withdraw`.
+    pub total_unbonding: Balance,
+}
+
+#[ext_contract(ext_fungible_token)]
+trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
+#[ext_contract(ext_self)]
+trait SelfCallbacks {
+    fn ping_validator_callback(&mut self, validator_id: AccountId);
+    fn withdraw_callback(&mut self, account_id: AccountId, released: U128);
+}
+
+#[near_bindgen]
+impl StakingContract {
+    #[init]
+    pub fn new(
+        owner_id: AccountId,
+        stake_public_key: PublicKey,
+        reward_fee_fraction: Ratio,
+        burn_fee_fraction: Ratio,
+    ) -> Self {
+        reward_fee_fraction.assert_valid();
+        burn_fee_fraction.assert_valid();
+        Self {
+            owner_id,
+            stake_public_key,
+            total_staked_balance: 0,
+            total_stake_shares: 0,
+            // Seeded from the actual account balance (which includes the
+            // deploy-time storage reserve), not from `total_staked_balance`
+            // (which starts at 0 regardless of that reserve) -- otherwise
+            // the first `ping` would see the reserve as a staking reward and
+            // burn/distribute it.
+            last_total_balance: env::account_locked_balance() + env::account_balance(),
+            accounts: LookupMap::new(b"a".to_vec()),
+            paused: false,
+            reward_fee_fraction,
+            burn_fee_fraction,
+            farms: Vector::new(b"f".to_vec()),
+            account_farm_rps_paid: LookupMap::new(b"r".to_vec()),
+            account_farm_pending: LookupMap::new(b"p".to_vec()),
+            unbond_epochs: DEFAULT_UNBOND_EPOCHS,
+            reward_queue: LookupMap::new(b"q".to_vec()),
+            reward_queue_head: 0,
+            reward_queue_tail: 0,
+            account_drop_cursor: LookupMap::new(b"c".to_vec()),
+            account_vesting: LookupMap::new(b"v".to_vec()),
+            validators: Vector::new(b"n".to_vec()),
+            total_unbonding: 0,
+        }
+    }
+
+    /// Deposits the attached NEAR into the pool and stakes it, minting
+    /// stNEAR to the caller at the current exchange rate. With no
+    /// validators configured the pool stakes directly under its own
+    /// account; otherwise the deposit is split across the validator set by
+    /// weight (see the `validators` module).
+    #[payable]
+    pub fn deposit_and_stake(&mut self) -> Promise {
+        assert!(!self.paused, "Staking is paused");
+        let account_id = env::predecessor_account_id();
+        let amount = env::attached_deposit();
+        assert!(amount > 0, "Must deposit a positive amount");
+
+        // Rounded down: minting a share more than the deposit buys would
+        // over-pay the depositor at the other holders' expense.
+        let num_shares = if self.total_staked_balance == 0 {
+            amount
+        } else {
+            (types::U256::from(self.total_stake_shares) * types::U256::from(amount)
+                / types::U256::from(self.total_staked_balance))
+            .as_u128()
+        };
+        assert!(num_shares > 0, "Deposit is too small to mint any stake shares");
+        self.internal_mint_shares(&account_id, num_shares);
+        self.total_staked_balance += amount;
+        // Tracks the account's actual balance, not `total_staked_balance` --
+        // keep it in lock-step with the deposit so `ping` only ever sees a
+        // reward once it's genuinely above what's been deposited so far.
+        self.last_total_balance += amount;
+
+        if self.validators.len() == 0 {
+            Promise::new(env::current_account_id()).stake(self.total_staked_balance, self.stake_public_key.clone())
+        } else {
+            self.internal_delegate_to_validators(amount)
+        }
+    }
+
+    /// Burns `amount` of the caller's stNEAR, queues the underlying NEAR for
+    /// unbonding (the entry matures independently of any other pending
+    /// unstake on the account, `unbond_epochs` epochs from now), and actually
+    /// reduces the amount delegated so it starts unlocking: restakes the
+    /// reduced total under this account's own key with no validators
+    /// configured, or unstakes the equivalent share from each validator.
+    pub fn unstake(&mut self, amount: U128) -> Promise {
+        assert!(!self.paused, "Staking is paused");
+        let account_id = env::predecessor_account_id();
+        let amount = amount.0;
+        assert!(amount > 0, "Must unstake a positive amount");
+
+        let num_shares = self.num_shares_from_staked_amount_rounded(amount);
+        self.internal_burn_shares(&account_id, num_shares);
+        self.total_staked_balance -= amount;
+
+        let mut account = self.internal_get_account(&account_id);
+        account.unbonding.push(UnbondEntry {
+            amount,
+            unlock_epoch: env::epoch_height() + self.unbond_epochs,
+        });
+        self.internal_save_account(&account_id, &account);
+        self.total_unbonding += amount;
+
+        if self.validators.len() == 0 {
+            Promise::new(env::current_account_id()).stake(self.total_staked_balance, self.stake_public_key.clone())
+        } else {
+            self.internal_unstake_from_validators(amount)
+        }
+    }
+
+    /// Releases every unbonding entry for the caller whose `unlock_epoch`
+    /// has passed, transferring their combined NEAR back to the caller. When
+    /// stake is delegated out, the matured NEAR has to be pulled back from
+    /// every validator via `withdraw_all` before it's ours to transfer.
+    pub fn withdraw(&mut self) -> Promise {
+        let account_id = env::predecessor_account_id();
+        let mut account = self.internal_get_account(&account_id);
+        let current_epoch = env::epoch_height();
+
+        let mut released = 0u128;
+        account.unbonding.retain(|entry| {
+            if entry.unlock_epoch <= current_epoch {
+                released += entry.amount;
+                false
+            } else {
+                true
+            }
+        });
+        assert!(released > 0, "No unbonded balance is available to withdraw yet");
+        self.internal_save_account(&account_id, &account);
+        self.total_unbonding -= released;
+
+        if self.validators.len() == 0 {
+            Promise::new(account_id).transfer(released)
+        } else {
+            self.internal_withdraw_all_validators().then(ext_self::withdraw_callback(
+                account_id,
+                U128(released),
+                env::current_account_id(),
+                0,
+                GAS_FOR_FARM_PAYOUT,
+            ))
+        }
+    }
+
+    /// Transfers `released` to `account_id`, but only once the validators'
+    /// `withdraw_all` has actually landed the funds in this account's own
+    /// balance -- `internal_withdraw_all_validators` only *requests* a
+    /// withdrawal, which is a no-op on the validator's side if its own
+    /// unbonding period hasn't elapsed yet. If the balance still isn't
+    /// there, restores the unbonding entry instead of transferring NEAR the
+    /// pool doesn't have. Doesn't `env::panic_str` in that case: a panic
+    /// here would roll back this receipt's own state changes too, including
+    /// the restorative push, silently losing the entry.
+    #[private]
+    pub fn withdraw_callback(&mut self, account_id: AccountId, released: U128) -> PromiseOrValue<()> {
+        let released = released.0;
+        if env::account_balance() < released + MIN_BALANCE_FOR_STORAGE {
+            let mut account = self.internal_get_account(&account_id);
+            account.unbonding.push(UnbondEntry {
+                amount: released,
+                unlock_epoch: env::epoch_height(),
+            });
+            self.internal_save_account(&account_id, &account);
+            self.total_unbonding += released;
+            return PromiseOrValue::Value(());
+        }
+        PromiseOrValue::Promise(Promise::new(account_id).transfer(released))
+    }
+
+    /// Recomputes `total_staked_balance` against the real validator stake to
+    /// pick up newly earned staking rewards, burning `burn_fee_fraction` of
+    /// the reward first and then taking the owner's `reward_fee_fraction` out
+    /// of what's left. Only meaningful for native self-staking: once stake is
+    /// delegated out to a validator set, this account's own locked/unlocked
+    /// balance no longer reflects staking rewards, so it's a no-op and
+    /// `ping_validator` is used per-validator instead.
+    pub fn ping(&mut self) {
+        if self.validators.len() > 0 {
+            return;
+        }
+        let total_balance =
+            env::account_locked_balance() + env::account_balance() - env::attached_deposit();
+        if total_balance <= self.last_total_balance {
+            self.last_total_balance = total_balance;
+            return;
+        }
+        let total_reward = total_balance - self.last_total_balance;
+        let burned = self.burn_fee_fraction.multiply(total_reward);
+        let reward_after_burn = total_reward - burned;
+        let owner_fee = self.reward_fee_fraction.multiply(reward_after_burn);
+
+        // Credit the stakers' share first, at the pre-fee exchange rate, so
+        // minting the owner's shares just below doesn't also dilute them out
+        // of the reward they're owed.
+        self.total_staked_balance += reward_after_burn - owner_fee;
+        self.last_total_balance = total_balance;
+        if owner_fee > 0 {
+            let owner_id = self.owner_id.clone();
+            // Rounded down, same as a deposit.
+            let num_shares = if self.total_staked_balance == 0 {
+                owner_fee
+            } else {
+                (types::U256::from(self.total_stake_shares) * types::U256::from(owner_fee)
+                    / types::U256::from(self.total_staked_balance))
+                .as_u128()
+            };
+            self.internal_mint_shares(&owner_id, num_shares);
+            self.total_staked_balance += owner_fee;
+        }
+    }
+
+    /// Cross-contract counterpart to `ping` for a delegated validator:
+    /// pulls this pool's `get_account_staked_balance` from `validator_id`
+    /// and folds any newly accrued reward into `total_staked_balance` in
+    /// `ping_validator_callback`, same as `ping` does for native staking.
+    pub fn ping_validator(&mut self, validator_id: AccountId) -> Promise {
+        self.internal_get_validator(&validator_id).expect("Validator not found");
+        validators::ext_staking_pool::get_account_staked_balance(
+            env::current_account_id(),
+            validator_id.clone(),
+            0,
+            validators::GAS_FOR_REDELEGATE_CALL,
+        )
+        .then(ext_self::ping_validator_callback(
+            validator_id,
+            env::current_account_id(),
+            0,
+            GAS_FOR_FARM_PAYOUT,
+        ))
+    }
+
+    #[private]
+    pub fn ping_validator_callback(&mut self, validator_id: AccountId, #[callback] reported_balance: U128) {
+        let (index, mut validator) = self
+            .internal_get_validator(&validator_id)
+            .expect("Validator not found");
+        if reported_balance.0 <= validator.staked {
+            return;
+        }
+        // Same burn-then-owner-fee ordering as `ping`, so the split between
+        // native and delegated stake doesn't change the payout for
+        // otherwise-identical rewards.
+        let total_reward = reported_balance.0 - validator.staked;
+        let burned = self.burn_fee_fraction.multiply(total_reward);
+        let reward_after_burn = total_reward - burned;
+        let owner_fee = self.reward_fee_fraction.multiply(reward_after_burn);
+
+        validator.staked = reported_balance.0;
+        self.validators.replace(index, &validator);
+        // Credit the stakers' share first, at the pre-fee exchange rate, so
+        // minting the owner's shares just below doesn't also dilute them out
+        // of the reward they're owed.
+        self.total_staked_balance += reward_after_burn - owner_fee;
+        if owner_fee > 0 {
+            let owner_id = self.owner_id.clone();
+            // Rounded down, same as a deposit.
+            let num_shares = if self.total_staked_balance == 0 {
+                owner_fee
+            } else {
+                (types::U256::from(self.total_stake_shares) * types::U256::from(owner_fee)
+                    / types::U256::from(self.total_staked_balance))
+                .as_u128()
+            };
+            self.internal_mint_shares(&owner_id, num_shares);
+            self.total_staked_balance += owner_fee;
+        }
+    }
+
+    /// Claims `account_id`'s pending reward from `farm_id`. For a plain farm
+    /// this pays out instantly via a cross-contract `ft_transfer`; for a
+    /// farm deployed with vesting terms, the reward is instead added to the
+    /// account's `VestingSchedule` and only becomes liquid through
+    /// `withdraw_vested`. Settles every farm for the account first (see
+    /// `internal_settle_farms`) so the claimed amount reflects reward
+    /// accrued while the account actually held its current share balance.
+    pub fn claim(&mut self, farm_id: u64) -> PromiseOrValue<()> {
+        let account_id = env::predecessor_account_id();
+        self.internal_settle_farms(&account_id);
+
+        let key = (account_id.clone(), farm_id);
+        let reward = self.account_farm_pending.remove(&key).unwrap_or_default();
+        assert!(reward > 0, "Nothing to claim");
+
+        let farm = self.farms.get(farm_id).expect("Farm not found");
+        if farm.vesting.is_some() {
+            let key = (account_id, farm_id);
+            let mut schedule = self.account_vesting.get(&key).unwrap_or_default();
+            schedule.total += reward;
+            self.account_vesting.insert(&key, &schedule);
+            PromiseOrValue::Value(())
+        } else {
+            PromiseOrValue::Promise(ext_fungible_token::ft_transfer(
+                account_id,
+                U128(reward),
+                None,
+                farm.token_id,
+                1,
+                GAS_FOR_FARM_PAYOUT,
+            ))
+        }
+    }
+
+    /// Releases whatever portion of `account_id`'s vested rewards from
+    /// `farm_id` has unlocked so far, per the farm's cliff + period-count
+    /// terms, and pays it out via `ft_transfer`.
+    pub fn withdraw_vested(&mut self, farm_id: u64) -> Promise {
+        let account_id = env::predecessor_account_id();
+        let farm = self.farms.get(farm_id).expect("Farm not found");
+        let vesting = farm.vesting.expect("Farm has no vesting terms");
+
+        let key = (account_id.clone(), farm_id);
+        let mut schedule = self.account_vesting.get(&key).unwrap_or_default();
+        let releasable = schedule.releasable(env::block_timestamp(), vesting.cliff, vesting.end, vesting.periods);
+        assert!(releasable > 0, "Nothing is vested yet");
+
+        schedule.claimed += releasable;
+        self.account_vesting.insert(&key, &schedule);
+
+        ext_fungible_token::ft_transfer(
+            account_id,
+            U128(releasable),
+            None,
+            farm.token_id,
+            1,
+            GAS_FOR_FARM_PAYOUT,
+        )
+    }
+
+    /// Pays out every reward-queue drop owed to the caller, one `ft_transfer`
+    /// per distinct reward token. Settles any drop since the account's last
+    /// settlement into `Account::drop_pending` first (see
+    /// `internal_settle_drops`), then drains that accumulator, so a claim
+    /// always pays out exactly what was settled against the share count the
+    /// account held at the time, regardless of deposits/withdrawals since.
+    pub fn claim_drop_rewards(&mut self) -> Promise {
+        let account_id = env::predecessor_account_id();
+        self.internal_settle_drops(&account_id);
+
+        let mut account = self.internal_get_account(&account_id);
+        let owed = std::mem::take(&mut account.drop_pending);
+        assert!(!owed.is_empty(), "Nothing to claim");
+        self.internal_save_account(&account_id, &account);
+
+        let mut payouts = owed.into_iter();
+        let (first_token, first_amount) = payouts.next().unwrap();
+        let mut promise = ext_fungible_token::ft_transfer(
+            account_id.clone(),
+            U128(first_amount),
+            None,
+            first_token,
+            1,
+            GAS_FOR_FARM_PAYOUT,
+        );
+        for (token_id, amount) in payouts {
+            promise = promise.and(ext_fungible_token::ft_transfer(
+                account_id.clone(),
+                U128(amount),
+                None,
+                token_id,
+                1,
+                GAS_FOR_FARM_PAYOUT,
+            ));
+        }
+        promise
+    }
+}
+
+/// Receiver side of NEP-141 `ft_transfer_call`: lets the owner fund a new
+/// time-linear farm by sending reward tokens to the pool with a JSON `msg`
+/// of the shape `{"name", "start_date", "end_date"}` (optionally plus
+/// `"cliff_date"`, `"vesting_end_date"`, `"period_count"` to make claims
+/// vest instead of paying out instantly), or push a one-off reward-queue
+/// drop with `{"drop": true}`.
+#[near_bindgen]
+impl StakingContract {
+    pub fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+        assert_eq!(sender_id, self.owner_id, "Only the owner can deploy a farm");
+        let token_id = env::predecessor_account_id();
+
+        if reward_queue::is_reward_drop(&msg) {
+            self.internal_push_reward_event(token_id, amount.0);
+            return PromiseOrValue::Value(U128(0));
+        }
+
+        let params: FarmDeployMsg = near_sdk::serde_json::from_str(&msg).expect("Invalid farm msg");
+        let vesting = match (params.cliff_date, params.vesting_end_date, params.period_count) {
+            (None, None, None) => None,
+            (Some(cliff_date), Some(vesting_end_date), Some(period_count)) => Some(farm::VestingTerms {
+                cliff: cliff_date.0,
+                end: vesting_end_date.0,
+                periods: period_count,
+            }),
+            _ => env::panic_str(
+                "cliff_date, vesting_end_date and period_count must be given together or not at all",
+            ),
+        };
+        let farm = Farm::new(params.name, token_id, amount.0, params.start_date.0, params.end_date.0, vesting);
+        self.farms.push(&farm);
+        PromiseOrValue::Value(U128(0))
+    }
+}
+
+#[derive(near_sdk::serde::Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct FarmDeployMsg {
+    name: String,
+    start_date: U64,
+    end_date: U64,
+    #[serde(default)]
+    cliff_date: Option<U64>,
+    #[serde(default)]
+    vesting_end_date: Option<U64>,
+    #[serde(default)]
+    period_count: Option<u32>,
+}
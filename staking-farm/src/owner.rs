@@ -0,0 +1,162 @@
+use near_sdk::json_types::U128;
+use near_sdk::near_bindgen;
+
+use crate::types::{Ratio, ValidatorInfo};
+use crate::validators::{ext_staking_pool, GAS_FOR_REDELEGATE_CALL};
+use crate::{MIN_BALANCE_FOR_STORAGE, *};
+
+#[near_bindgen]
+impl StakingContract {
+    pub fn set_reward_fee_fraction(&mut self, reward_fee_fraction: Ratio) {
+        self.assert_owner();
+        reward_fee_fraction.assert_valid();
+        self.reward_fee_fraction = reward_fee_fraction;
+    }
+
+    pub fn set_burn_fee_fraction(&mut self, burn_fee_fraction: Ratio) {
+        self.assert_owner();
+        burn_fee_fraction.assert_valid();
+        self.burn_fee_fraction = burn_fee_fraction;
+    }
+
+    /// Sets the number of epochs an `unstake`d amount must wait before
+    /// `withdraw` releases it. Only affects entries created after the call;
+    /// already-queued `UnbondEntry`s keep the `unlock_epoch` they were
+    /// given.
+    pub fn set_unbond_epochs(&mut self, unbond_epochs: near_sdk::EpochHeight) {
+        self.assert_owner();
+        assert!(unbond_epochs > 0, "unbond_epochs must be positive");
+        self.unbond_epochs = unbond_epochs;
+    }
+
+    pub fn pause(&mut self) {
+        self.assert_owner();
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.assert_owner();
+        self.paused = false;
+    }
+
+    /// Adds `account_id` (an existing whitelisted NEAR staking pool) to the
+    /// delegation set with the given `weight`. Newly deposited NEAR is
+    /// split across validators proportional to weight; call `rebalance`
+    /// afterwards to also shift already-delegated stake toward the new
+    /// target split.
+    pub fn add_validator(&mut self, account_id: AccountId, weight: u16) {
+        self.assert_owner();
+        assert!(weight > 0, "weight must be positive");
+        assert!(
+            self.internal_get_validator(&account_id).is_none(),
+            "Validator is already in the set"
+        );
+        self.validators.push(&ValidatorInfo {
+            account_id,
+            weight,
+            staked: 0,
+            pending_unstake: 0,
+            unstake_available_epoch: 0,
+        });
+    }
+
+    /// Removes `account_id` from the delegation set. The validator must
+    /// already be fully unstaked and settled (`staked == 0`, and no
+    /// `pending_unstake` still working through the validator's own unbonding
+    /// period) so removal never strands delegated NEAR.
+    pub fn remove_validator(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        let (index, validator) = self
+            .internal_get_validator(&account_id)
+            .expect("Validator not found");
+        assert_eq!(validator.staked, 0, "Validator must be fully unstaked before removal");
+        assert_eq!(
+            validator.pending_unstake, 0,
+            "Validator has a pending unstake still working through its unbonding period"
+        );
+        self.validators.swap_remove(index);
+    }
+
+    /// Moves delegated stake toward each validator's target weight. Since an
+    /// unstaked validator's NEAR isn't spendable until its own unbonding
+    /// period elapses, this is staged across multiple calls/epochs rather
+    /// than moved in one pass:
+    /// 1. Pull back (`withdraw_all`) any validator's unstake that has
+    ///    matured, if any -- once that settles, its NEAR sits idle in this
+    ///    account ready to redeploy.
+    /// 2. Otherwise, if idle NEAR is on hand (e.g. from a prior step 1),
+    ///    deposit it into the first validator that is below target (one
+    ///    validator per call).
+    /// 3. Otherwise, unstake the overage from above-target validators, to be
+    ///    picked up by step 1 on a later call once it matures.
+    pub fn rebalance(&mut self) -> Promise {
+        self.assert_owner();
+        let total_weight = self.internal_total_validator_weight();
+        assert!(total_weight > 0, "No validators configured");
+
+        let mut promise: Option<Promise> = None;
+        let current_epoch = env::epoch_height();
+        for index in 0..self.validators.len() {
+            let mut validator = self.validators.get(index).unwrap();
+            if validator.pending_unstake > 0 && current_epoch >= validator.unstake_available_epoch {
+                validator.pending_unstake = 0;
+                let call = ext_staking_pool::withdraw_all(validator.account_id.clone(), 0, GAS_FOR_REDELEGATE_CALL);
+                self.validators.replace(index, &validator);
+                promise = Some(match promise {
+                    Some(p) => p.and(call),
+                    None => call,
+                });
+            }
+        }
+        if let Some(promise) = promise {
+            return promise;
+        }
+
+        // Exclude the storage reserve and any NEAR already owed to
+        // depositors waiting on `withdraw` -- only what's genuinely spare is
+        // safe to redeploy.
+        let idle = env::account_balance()
+            .saturating_sub(MIN_BALANCE_FOR_STORAGE)
+            .saturating_sub(self.total_unbonding);
+        if idle > 0 {
+            for index in 0..self.validators.len() {
+                let mut validator = self.validators.get(index).unwrap();
+                let target = self.total_staked_balance * validator.weight as u128 / total_weight as u128;
+                if validator.staked >= target || validator.pending_unstake > 0 {
+                    continue;
+                }
+                let deposit = idle.min(target - validator.staked);
+                if deposit == 0 {
+                    continue;
+                }
+                validator.staked += deposit;
+                let call =
+                    ext_staking_pool::deposit_and_stake(validator.account_id.clone(), deposit, GAS_FOR_REDELEGATE_CALL);
+                self.validators.replace(index, &validator);
+                return match promise {
+                    Some(p) => p.and(call),
+                    None => call,
+                };
+            }
+        }
+
+        for index in 0..self.validators.len() {
+            let mut validator = self.validators.get(index).unwrap();
+            let target = self.total_staked_balance * validator.weight as u128 / total_weight as u128;
+            if validator.staked <= target || validator.pending_unstake > 0 {
+                continue;
+            }
+            let overage = validator.staked - target;
+            validator.staked -= overage;
+            validator.pending_unstake += overage;
+            validator.unstake_available_epoch = current_epoch + self.unbond_epochs;
+            let call = ext_staking_pool::unstake(U128(overage), validator.account_id.clone(), 0, GAS_FOR_REDELEGATE_CALL);
+            self.validators.replace(index, &validator);
+            promise = Some(match promise {
+                Some(p) => p.and(call),
+                None => call,
+            });
+        }
+        promise.unwrap_or_else(|| Promise::new(env::current_account_id()))
+    }
+}
@@ -0,0 +1,120 @@
+//! A fixed-capacity ring buffer of ad-hoc reward drops, for sporadic
+//! airdrop-style payouts that don't justify deploying a whole time-linear
+//! farm. Each account only remembers a `cursor` (the global index of the
+//! next event it hasn't processed yet); claiming walks forward from there
+//! to the queue head.
+//!
+//! Events are keyed by a monotonically increasing global index rather than
+//! stored in a `near_sdk::collections::Vector`, since a `Vector` has no
+//! cheap "drop the oldest element" operation -- here eviction is just
+//! advancing `reward_queue_tail` and removing the one `LookupMap` entry it
+//! used to point at.
+
+use near_sdk::{AccountId, Balance};
+
+use crate::types::{RewardEvent, U256};
+use crate::*;
+
+/// Once the queue holds this many events, pushing a new one evicts the
+/// oldest. Keeps per-account catch-up (`claim_drop_rewards`) bounded no
+/// matter how long an account has been idle.
+pub const REWARD_QUEUE_CAPACITY: u64 = 64;
+
+impl StakingContract {
+    /// Pushes a new reward drop, evicting the oldest event once the queue
+    /// is at capacity.
+    pub(crate) fn internal_push_reward_event(&mut self, token_id: AccountId, amount: Balance) {
+        let event = RewardEvent {
+            token_id,
+            amount,
+            total_shares_snapshot: self.total_stake_shares,
+        };
+        self.reward_queue.insert(&self.reward_queue_head, &event);
+        self.reward_queue_head += 1;
+        if self.reward_queue_head - self.reward_queue_tail > REWARD_QUEUE_CAPACITY {
+            self.reward_queue.remove(&self.reward_queue_tail);
+            self.reward_queue_tail += 1;
+        }
+    }
+
+    /// The index an account's cursor should start at: skips every event
+    /// that was already in the queue before the account had any stake, so
+    /// an account that joined after a drop gets nothing from it.
+    pub(crate) fn internal_account_drop_cursor(&self, account_id: &AccountId) -> u64 {
+        self.account_drop_cursor
+            .get(account_id)
+            .unwrap_or(self.reward_queue_head)
+            // Evicted events are simply skipped: a cursor can never point
+            // earlier than the oldest event still in the queue.
+            .max(self.reward_queue_tail)
+    }
+
+    /// Settles every drop between `account_id`'s cursor and the current
+    /// queue head into `Account::drop_pending`, then advances the cursor to
+    /// the head. Must run before `account_id`'s `stake_shares` changes (mint,
+    /// burn, or either side of an `ft_transfer`) -- that's what guarantees
+    /// `internal_unclaimed_drop_rewards` always credits a queue window
+    /// against the share count the account actually held throughout it,
+    /// rather than its share count *now*, which an account could inflate by
+    /// depositing more after the drop but before claiming.
+    pub(crate) fn internal_settle_drops(&mut self, account_id: &AccountId) {
+        let owed = self.internal_unclaimed_drop_rewards(account_id);
+        if !owed.is_empty() {
+            let mut account = self.internal_get_account(account_id);
+            for (token_id, amount) in owed {
+                match account.drop_pending.iter_mut().find(|(t, _)| t == &token_id) {
+                    Some((_, total)) => *total += amount,
+                    None => account.drop_pending.push((token_id, amount)),
+                }
+            }
+            self.internal_save_account(account_id, &account);
+        }
+        self.internal_advance_drop_cursor(account_id);
+    }
+
+    /// Sums, per reward token, the drop rewards owed to `account_id` between
+    /// its cursor and the current queue head.
+    pub(crate) fn internal_unclaimed_drop_rewards(&self, account_id: &AccountId) -> Vec<(AccountId, Balance)> {
+        let account = self.internal_get_account(account_id);
+        let cursor = self.internal_account_drop_cursor(account_id);
+        let mut totals: Vec<(AccountId, Balance)> = Vec::new();
+        for index in cursor..self.reward_queue_head {
+            let event = match self.reward_queue.get(&index) {
+                Some(event) => event,
+                None => continue,
+            };
+            if event.total_shares_snapshot == 0 {
+                continue;
+            }
+            let reward = (U256::from(event.amount) * U256::from(account.stake_shares)
+                / U256::from(event.total_shares_snapshot))
+            .as_u128();
+            if reward == 0 {
+                continue;
+            }
+            match totals.iter_mut().find(|(token_id, _)| token_id == &event.token_id) {
+                Some((_, total)) => *total += reward,
+                None => totals.push((event.token_id, reward)),
+            }
+        }
+        totals
+    }
+
+    pub(crate) fn internal_advance_drop_cursor(&mut self, account_id: &AccountId) {
+        self.account_drop_cursor.insert(account_id, &self.reward_queue_head);
+    }
+}
+
+/// `msg` payload recognized by `ft_on_transfer` for a one-off reward drop,
+/// as opposed to deploying a new time-linear farm.
+#[derive(near_sdk::serde::Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RewardDropMsg {
+    pub drop: bool,
+}
+
+pub(crate) fn is_reward_drop(msg: &str) -> bool {
+    near_sdk::serde_json::from_str::<RewardDropMsg>(msg)
+        .map(|parsed| parsed.drop)
+        .unwrap_or(false)
+}
@@ -0,0 +1,140 @@
+//! The pool's own NEP-141 surface: every staked NEAR is represented by a
+//! "stake share" (stNEAR) token minted to the depositor. The token's value
+//! floats with `total_staked_balance / total_stake_shares`, so holders don't
+//! need to call back into the pool to realize staking (and farm-burn)
+//! rewards -- their balance in yoctoNEAR-equivalent terms simply grows.
+
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_one_yocto, env, log, near_bindgen, AccountId, Balance, Gas, Promise, PromiseOrValue,
+};
+
+use crate::*;
+
+const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas(30_000_000_000_000);
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
+
+/// Cost of the `Account` storage record created by `storage_deposit`. There
+/// is no `storage_unregister`, so it is never refunded.
+pub const STORAGE_DEPOSIT_COST: Balance = 1_250_000_000_000_000_000_000;
+
+pub(crate) fn emit_ft_mint(account_id: &AccountId, amount: Balance) {
+    log!("Mint {} stNEAR shares to {}", amount, account_id);
+}
+
+pub(crate) fn emit_ft_burn(account_id: &AccountId, amount: Balance) {
+    log!("Burn {} stNEAR shares from {}", amount, account_id);
+}
+
+#[near_bindgen]
+impl StakingContract {
+    /// Registers `account_id` (or the caller) so it can hold stNEAR. Mirrors
+    /// the standard NEP-145 storage deposit; the pool doesn't charge more
+    /// than the cost of the `Account` record since accounts are also used
+    /// for staking bookkeeping.
+    #[payable]
+    pub fn storage_deposit(&mut self, account_id: Option<AccountId>) -> near_sdk::json_types::U128 {
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let deposit = env::attached_deposit();
+        assert!(
+            deposit >= STORAGE_DEPOSIT_COST,
+            "Attached deposit must cover the storage cost of {} yoctoNEAR",
+            STORAGE_DEPOSIT_COST
+        );
+        // Keep only the record's cost; an already registered account pays
+        // nothing, and any excess goes back to the caller.
+        let refund = if self.accounts.get(&account_id).is_none() {
+            self.accounts.insert(&account_id, &Account::default());
+            deposit - STORAGE_DEPOSIT_COST
+        } else {
+            deposit
+        };
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+        U128(STORAGE_DEPOSIT_COST)
+    }
+
+    /// Raw stNEAR share balance -- this does *not* track the exchange rate,
+    /// so it stays put across a `ping` even as the NEAR it's redeemable for
+    /// (see `get_account_staked_balance`) grows with staking rewards.
+    pub fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+        U128(self.internal_get_account(&account_id).stake_shares)
+    }
+
+    pub fn ft_total_supply(&self) -> U128 {
+        U128(self.total_stake_shares)
+    }
+
+    #[payable]
+    pub fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
+        assert_one_yocto();
+        let _ = memo;
+        self.internal_ft_transfer(&env::predecessor_account_id(), &receiver_id, amount.0);
+    }
+
+    #[payable]
+    pub fn ft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        assert_one_yocto();
+        let _ = memo;
+        let sender_id = env::predecessor_account_id();
+        self.internal_ft_transfer(&sender_id, &receiver_id, amount.0);
+        PromiseOrValue::Promise(
+            Promise::new(receiver_id.clone())
+                .function_call(
+                    "ft_on_transfer".to_string(),
+                    near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
+                        "sender_id": sender_id,
+                        "amount": amount,
+                        "msg": msg,
+                    }))
+                    .unwrap(),
+                    0,
+                    GAS_FOR_FT_TRANSFER_CALL,
+                )
+                .then(Promise::new(env::current_account_id()).function_call(
+                    "ft_resolve_transfer".to_string(),
+                    near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
+                        "sender_id": sender_id,
+                        "receiver_id": receiver_id,
+                        "amount": amount,
+                    }))
+                    .unwrap(),
+                    0,
+                    GAS_FOR_RESOLVE_TRANSFER,
+                )),
+        )
+    }
+
+    /// Refunds the sender the unused part of a transfer if `ft_on_transfer`
+    /// reported it (standard NEP-141 resolve callback).
+    #[private]
+    pub fn ft_resolve_transfer(&mut self, sender_id: AccountId, receiver_id: AccountId, amount: U128) -> U128 {
+        // `ft_on_transfer` returns the amount it did NOT use; a failed or
+        // unparsable result means none of it was used.
+        let refund = match env::promise_result(0) {
+            near_sdk::PromiseResult::Successful(value) => near_sdk::serde_json::from_slice::<U128>(&value)
+                .map(|unused| unused.0.min(amount.0))
+                .unwrap_or(amount.0),
+            _ => amount.0,
+        };
+        if refund > 0 {
+            self.internal_ft_transfer(&receiver_id, &sender_id, refund);
+        }
+        U128(amount.0 - refund)
+    }
+
+    /// `amount` is in stNEAR shares, matching `ft_balance_of`.
+    pub(crate) fn internal_ft_transfer(&mut self, sender_id: &AccountId, receiver_id: &AccountId, amount: Balance) {
+        assert_ne!(sender_id, receiver_id, "Sender and receiver must differ");
+        assert!(amount > 0, "The transfer amount must be a positive number");
+        self.internal_burn_shares(sender_id, amount);
+        self.internal_mint_shares(receiver_id, amount);
+    }
+}
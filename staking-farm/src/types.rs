@@ -0,0 +1,121 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{Balance, EpochHeight};
+use uint::construct_uint;
+
+construct_uint! {
+    /// 256-bit unsigned integer, used for overflow-safe share/reward math.
+    pub struct U256(4);
+}
+
+/// A fee expressed as `numerator / denominator`, e.g. the owner's reward cut
+/// or the burn cut taken out of staking rewards on `ping`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Ratio {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl Ratio {
+    pub fn assert_valid(&self) {
+        assert_ne!(self.denominator, 0, "Denominator must be a positive number");
+        assert!(
+            self.numerator <= self.denominator,
+            "The numerator must be less or equal to the denominator"
+        );
+    }
+
+    pub fn multiply(&self, value: Balance) -> Balance {
+        (U256::from(value) * U256::from(self.numerator) / U256::from(self.denominator)).as_u128()
+    }
+}
+
+/// One validator in the pool's delegation set, and how much weight (out of
+/// the sum of all validators' weights) it should hold of the pool's total
+/// staked NEAR.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorInfo {
+    pub account_id: near_sdk::AccountId,
+    pub weight: u16,
+    /// NEAR this pool believes it currently has delegated to this
+    /// validator. Updated optimistically when a rebalance promise is sent;
+    /// `ping_validator` reconciles it against the validator's own
+    /// `get_account_staked_balance` for this pool's account.
+    pub staked: Balance,
+    /// NEAR unstaked from this validator by `rebalance` that hasn't been
+    /// pulled back yet -- it isn't spendable (and can't be redeployed to an
+    /// under-weight validator) until `unstake_available_epoch` passes and
+    /// `withdraw_all` brings it into this pool's own balance.
+    pub pending_unstake: Balance,
+    pub unstake_available_epoch: EpochHeight,
+}
+
+/// A single ad-hoc reward drop pushed into the reward queue by
+/// `ft_on_transfer` (see the `reward_queue` module). `total_shares_snapshot`
+/// pins the stNEAR supply at push time so that crediting an account later
+/// stays proportional to what it held when the drop happened.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+pub struct RewardEvent {
+    pub token_id: near_sdk::AccountId,
+    pub amount: Balance,
+    pub total_shares_snapshot: Balance,
+}
+
+/// Tracks one account's claims from a vesting farm: `total` is claimed-but-
+/// locked rewards accumulated so far, `claimed` is how much of that has
+/// already been released via `withdraw_vested`.
+#[derive(BorshDeserialize, BorshSerialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct VestingSchedule {
+    pub total: Balance,
+    pub claimed: Balance,
+}
+
+impl VestingSchedule {
+    /// `floor((now - cliff) / (end - cliff) * periods) / periods * total`,
+    /// clamped to zero before the cliff and to `total` after `end`, minus
+    /// whatever has already been released.
+    pub fn releasable(&self, now: u64, cliff: u64, end: u64, periods: u32) -> Balance {
+        if now < cliff {
+            return 0;
+        }
+        let vested = if now >= end {
+            self.total
+        } else {
+            let elapsed_periods = (now - cliff) as u128 * periods as u128 / (end - cliff) as u128;
+            self.total * elapsed_periods / periods as u128
+        };
+        vested.saturating_sub(self.claimed)
+    }
+}
+
+/// A single pending unstake, maturing independently of any other unbonding
+/// entry for the same account.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq, Eq)]
+pub struct UnbondEntry {
+    pub amount: Balance,
+    pub unlock_epoch: EpochHeight,
+}
+
+/// Per-delegator bookkeeping. `stake_shares` doubles as the account's stNEAR
+/// (liquid staking token) balance: the pool never stores a NEAR amount
+/// directly, it derives it from the share count and the pool-wide exchange
+/// rate so that rewards (and burns) are reflected for every holder at once.
+#[derive(BorshDeserialize, BorshSerialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct Account {
+    /// Number of "stake shares" (== stNEAR tokens) owned by this account.
+    pub stake_shares: Balance,
+    /// NEAR that has been unstaked but isn't withdrawable yet. A plain `Vec`
+    /// is enough here: an account realistically only ever has a handful of
+    /// unbonding entries outstanding at once.
+    pub unbonding: Vec<UnbondEntry>,
+    /// Reward-queue drops already settled by `internal_settle_drops` (see
+    /// the `reward_queue` module) but not yet paid out by
+    /// `claim_drop_rewards`, per reward token. Settling on every
+    /// `stake_shares` change -- mint, burn, or an `ft_transfer` -- guarantees
+    /// the share count used to credit a given queue window never changes
+    /// mid-window, which is what keeps per-account credits from a drop
+    /// summing to the dropped `amount`, less only what each account's
+    /// rounded-down credit drops.
+    pub drop_pending: Vec<(near_sdk::AccountId, Balance)>,
+}
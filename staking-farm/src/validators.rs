@@ -0,0 +1,128 @@
+//! Generalizes the pool from staking with a single `stake_public_key` into
+//! delegating across a weighted set of underlying NEAR staking pools,
+//! mirroring Solana stake-pool's `ValidatorStakeList`. When no validators
+//! have been added, the pool falls back to its original behavior of
+//! staking directly under its own account (see `StakingContract::new`'s
+//! `stake_public_key`), so existing single-validator deployments are
+//! unaffected.
+
+use near_sdk::json_types::U128;
+use near_sdk::{ext_contract, AccountId, Balance, Gas};
+
+use crate::types::ValidatorInfo;
+use crate::*;
+
+pub const GAS_FOR_REDELEGATE_CALL: Gas = Gas(40_000_000_000_000);
+
+#[ext_contract(ext_staking_pool)]
+pub trait StakingPool {
+    fn deposit_and_stake(&mut self);
+    fn unstake(&mut self, amount: U128);
+    fn withdraw_all(&mut self);
+    fn get_account_staked_balance(&self, account_id: AccountId) -> U128;
+}
+
+impl StakingContract {
+    pub(crate) fn internal_get_validator(&self, account_id: &AccountId) -> Option<(u64, ValidatorInfo)> {
+        for index in 0..self.validators.len() {
+            let validator = self.validators.get(index).unwrap();
+            if &validator.account_id == account_id {
+                return Some((index, validator));
+            }
+        }
+        None
+    }
+
+    pub(crate) fn internal_total_validator_weight(&self) -> u32 {
+        (0..self.validators.len())
+            .map(|index| self.validators.get(index).unwrap().weight as u32)
+            .sum()
+    }
+
+    /// Splits `amount` across every validator proportional to its weight,
+    /// handing the remainder left over from integer division to the first
+    /// validator so the split always sums exactly to `amount`.
+    pub(crate) fn internal_split_by_weight(&self, amount: Balance) -> Vec<(u64, Balance)> {
+        let total_weight = self.internal_total_validator_weight();
+        if total_weight == 0 {
+            return Vec::new();
+        }
+        let mut split = Vec::new();
+        let mut distributed = 0u128;
+        for index in 0..self.validators.len() {
+            let validator = self.validators.get(index).unwrap();
+            let share = amount * validator.weight as u128 / total_weight as u128;
+            split.push((index, share));
+            distributed += share;
+        }
+        if let Some(first) = split.first_mut() {
+            first.1 += amount - distributed;
+        }
+        split
+    }
+
+    /// Delegates `amount` across the validator set by weight via
+    /// cross-contract `deposit_and_stake` calls, updating each validator's
+    /// tracked `staked` amount optimistically.
+    pub(crate) fn internal_delegate_to_validators(&mut self, amount: Balance) -> Promise {
+        let mut promise: Option<Promise> = None;
+        for (index, share) in self.internal_split_by_weight(amount) {
+            if share == 0 {
+                continue;
+            }
+            let mut validator = self.validators.get(index).unwrap();
+            validator.staked += share;
+            let validator_id = validator.account_id.clone();
+            self.validators.replace(index, &validator);
+
+            let call = ext_staking_pool::deposit_and_stake(validator_id, share, GAS_FOR_REDELEGATE_CALL);
+            promise = Some(match promise {
+                Some(p) => p.and(call),
+                None => call,
+            });
+        }
+        promise.unwrap_or_else(|| Promise::new(env::current_account_id()))
+    }
+
+    /// Unstakes `amount` from the validator set by weight, same split as
+    /// `internal_delegate_to_validators`, updating each validator's tracked
+    /// `staked` amount optimistically. The NEAR only becomes spendable once
+    /// `internal_withdraw_all_validators` pulls it back after each
+    /// validator's own unbonding period elapses.
+    pub(crate) fn internal_unstake_from_validators(&mut self, amount: Balance) -> Promise {
+        let mut promise: Option<Promise> = None;
+        for (index, share) in self.internal_split_by_weight(amount) {
+            if share == 0 {
+                continue;
+            }
+            let mut validator = self.validators.get(index).unwrap();
+            validator.staked -= share;
+            validator.pending_unstake += share;
+            validator.unstake_available_epoch = env::epoch_height() + self.unbond_epochs;
+            let validator_id = validator.account_id.clone();
+            self.validators.replace(index, &validator);
+
+            let call = ext_staking_pool::unstake(U128(share), validator_id, 0, GAS_FOR_REDELEGATE_CALL);
+            promise = Some(match promise {
+                Some(p) => p.and(call),
+                None => call,
+            });
+        }
+        promise.unwrap_or_else(|| Promise::new(env::current_account_id()))
+    }
+
+    /// Pulls back whatever NEAR has finished unbonding on every validator,
+    /// into this pool's own account balance.
+    pub(crate) fn internal_withdraw_all_validators(&mut self) -> Promise {
+        let mut promise: Option<Promise> = None;
+        for index in 0..self.validators.len() {
+            let validator_id = self.validators.get(index).unwrap().account_id;
+            let call = ext_staking_pool::withdraw_all(validator_id, 0, GAS_FOR_REDELEGATE_CALL);
+            promise = Some(match promise {
+                Some(p) => p.and(call),
+                None => call,
+            });
+        }
+        promise.unwrap_or_else(|| Promise::new(env::current_account_id()))
+    }
+}
@@ -0,0 +1,101 @@
+use near_sdk::json_types::U128;
+use near_sdk::{near_bindgen, AccountId, Balance, EpochHeight};
+
+use crate::*;
+
+#[near_bindgen]
+impl StakingContract {
+    /// Staked NEAR currently redeemable for `account_id`'s stNEAR balance,
+    /// i.e. `stake_shares * total_staked_balance / total_stake_shares`.
+    pub fn get_account_staked_balance(&self, account_id: AccountId) -> U128 {
+        let account = self.internal_get_account(&account_id);
+        U128(self.staked_amount_from_num_shares(account.stake_shares))
+    }
+
+    /// Staked balance plus any NEAR still working through the unbonding
+    /// queue (see `get_account_unbonding` for the per-entry breakdown).
+    pub fn get_account_total_balance(&self, account_id: AccountId) -> U128 {
+        let staked = self.get_account_staked_balance(account_id.clone()).0;
+        let unbonding: Balance = self
+            .internal_get_account(&account_id)
+            .unbonding
+            .iter()
+            .map(|entry| entry.amount)
+            .sum();
+        U128(staked + unbonding)
+    }
+
+    /// Total NEAR the pool holds staked for its stNEAR holders, as its own
+    /// running total (delegated across the validator set, or staked natively
+    /// if none are configured); it still counts NEAR that `rebalance` has
+    /// unstaked but not yet redeployed -- every account's
+    /// `get_account_staked_balance` is a share of this figure.
+    pub fn get_total_staked_balance(&self) -> U128 {
+        U128(self.total_staked_balance)
+    }
+
+    pub fn get_owner_id(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pending unbonding entries for `account_id` that haven't been
+    /// withdrawn yet, as `(amount, unlock_epoch)` pairs.
+    pub fn get_account_unbonding(&self, account_id: AccountId) -> Vec<(U128, EpochHeight)> {
+        self.internal_get_account(&account_id)
+            .unbonding
+            .iter()
+            .map(|entry| (U128(entry.amount), entry.unlock_epoch))
+            .collect()
+    }
+
+    /// Sum of every reward-queue drop owed to `account_id`, per reward
+    /// token, since its last `claim_drop_rewards` call: whatever is already
+    /// settled into `Account::drop_pending`, plus the live delta that the
+    /// account's next settlement (on its next mint/burn/claim) would add.
+    pub fn get_unclaimed_drop_rewards(&self, account_id: AccountId) -> Vec<(AccountId, U128)> {
+        let mut totals = self.internal_get_account(&account_id).drop_pending;
+        for (token_id, amount) in self.internal_unclaimed_drop_rewards(&account_id) {
+            match totals.iter_mut().find(|(t, _)| t == &token_id) {
+                Some((_, total)) => *total += amount,
+                None => totals.push((token_id, amount)),
+            }
+        }
+        totals.into_iter().map(|(token_id, amount)| (token_id, U128(amount))).collect()
+    }
+
+    /// `account_id`'s vesting progress on `farm_id`, as
+    /// `(total claimed-but-locked, already released via withdraw_vested)`.
+    pub fn get_vesting(&self, account_id: AccountId, farm_id: u64) -> Option<(U128, U128)> {
+        self.account_vesting
+            .get(&(account_id, farm_id))
+            .map(|schedule| (U128(schedule.total), U128(schedule.claimed)))
+    }
+
+    /// The current delegation set: one `(account_id, weight, staked)` tuple
+    /// per validator, in the order they were added, except that
+    /// `remove_validator` moves the last validator into the removed one's slot.
+    pub fn get_validators(&self) -> Vec<(AccountId, u16, U128)> {
+        (0..self.validators.len())
+            .map(|index| {
+                let validator = self.validators.get(index).unwrap();
+                (validator.account_id, validator.weight, U128(validator.staked))
+            })
+            .collect()
+    }
+
+    pub fn get_unclaimed_reward(&self, account_id: AccountId, farm_id: u64) -> U128 {
+        // `distribute` is pure given (now, total_stake_shares), so it's safe
+        // to run against a clone here without persisting the result.
+        let mut farm = self.farms.get(farm_id).expect("Farm not found");
+        farm.distribute(near_sdk::env::block_timestamp(), self.total_stake_shares);
+        let account = self.internal_get_account(&account_id);
+        let key = (account_id, farm_id);
+        let rps_paid = self.account_farm_rps_paid.get(&key).unwrap_or_default();
+        let pending = self.account_farm_pending.get(&key).unwrap_or_default();
+        U128(pending + farm.unclaimed_reward(account.stake_shares, rps_paid))
+    }
+}
@@ -185,4 +185,234 @@ fn test_farm_and_burn() {
         other_balance3
     );
     assert!(other_balance4 > to_yocto("325") && other_balance4 < to_yocto("327"));
+}
+
+#[test]
+fn test_liquid_token_appreciates_with_rewards() {
+    let (root, pool) = setup();
+    let user1 = root.create_user(
+        AccountId::new_unchecked("user1".to_string()),
+        to_yocto("100000"),
+    );
+    assert_all_success(call!(
+        user1,
+        pool.deposit_and_stake(),
+        deposit = to_yocto("10000")
+    ));
+
+    let shares_before = view!(pool.ft_balance_of(user1.account_id())).unwrap_json::<U128>();
+    let value_before = view!(pool.get_account_staked_balance(user1.account_id())).unwrap_json::<U128>();
+    assert_eq!(shares_before.0, value_before.0, "1 share == 1 yoctoNEAR before any rewards");
+
+    wait_epoch(&root);
+    assert_all_success(call!(root, pool.ping()));
+
+    let shares_after = view!(pool.ft_balance_of(user1.account_id())).unwrap_json::<U128>();
+    let value_after = view!(pool.get_account_staked_balance(user1.account_id())).unwrap_json::<U128>();
+
+    // The stNEAR balance itself never moves on `ping` ...
+    assert_eq!(shares_before.0, shares_after.0);
+    // ... but the NEAR it's redeemable for grows as staking rewards accrue.
+    assert!(value_after.0 > value_before.0);
+}
+
+#[test]
+fn test_unbonding_queue_timelock() {
+    let (root, pool) = setup();
+    let user1 = root.create_user(
+        AccountId::new_unchecked("user1".to_string()),
+        to_yocto("100000"),
+    );
+    assert_all_success(call!(
+        user1,
+        pool.deposit_and_stake(),
+        deposit = to_yocto("10000")
+    ));
+    assert_all_success(call!(root, pool.set_unbond_epochs(2)));
+    assert_all_success(call!(
+        user1,
+        pool.unstake(U128(to_yocto("4000")))
+    ));
+
+    let unbonding = view!(pool.get_account_unbonding(user1.account_id()))
+        .unwrap_json::<Vec<(U128, u64)>>();
+    assert_eq!(unbonding.len(), 1);
+    assert_eq!(unbonding[0].0 .0, to_yocto("4000"));
+
+    // Still locked after one epoch: withdraw fails and the entry is untouched.
+    wait_epoch(&root);
+    assert!(!call!(user1, pool.withdraw()).is_ok());
+    let unbonding = view!(pool.get_account_unbonding(user1.account_id()))
+        .unwrap_json::<Vec<(U128, u64)>>();
+    assert_eq!(unbonding.len(), 1);
+
+    // Unlocked after the configured 2 epochs.
+    wait_epoch(&root);
+    assert_all_success(call!(user1, pool.withdraw()));
+    let unbonding = view!(pool.get_account_unbonding(user1.account_id()))
+        .unwrap_json::<Vec<(U128, u64)>>();
+    assert!(unbonding.is_empty());
+}
+
+#[test]
+fn test_reward_queue_drop() {
+    let (root, pool) = setup();
+    let user1 = root.create_user(
+        AccountId::new_unchecked("user1".to_string()),
+        to_yocto("100000"),
+    );
+    assert_all_success(call!(
+        user1,
+        pool.deposit_and_stake(),
+        deposit = to_yocto("10000")
+    ));
+
+    let msg = serde_json::to_string(&json!({ "drop": true })).unwrap();
+    assert_all_success(root.call(
+        AccountId::new_unchecked(TOKEN_ACCOUNT_ID.to_string()),
+        "ft_transfer_call",
+        &serde_json::to_vec(&json!({ "receiver_id": STAKING_POOL_ACCOUNT_ID, "amount": to_yocto("1000").to_string(), "msg": msg })).unwrap(),
+        near_sdk_sim::DEFAULT_GAS,
+        1,
+    ));
+
+    // user1 held all the stake at drop time, so it should be owed ~all of it.
+    let owed1 = view!(pool.get_unclaimed_drop_rewards(user1.account_id()))
+        .unwrap_json::<Vec<(AccountId, U128)>>();
+    assert_eq!(owed1.len(), 1);
+    assert!(owed1[0].1 .0 > to_yocto("999") && owed1[0].1 .0 <= to_yocto("1000"));
+
+    // A late joiner wasn't staked when the drop landed, so it gets nothing.
+    let user2 = root.create_user(
+        AccountId::new_unchecked("user2".to_string()),
+        to_yocto("100000"),
+    );
+    assert_all_success(call!(
+        user2,
+        pool.deposit_and_stake(),
+        deposit = to_yocto("10000")
+    ));
+    let owed2 = view!(pool.get_unclaimed_drop_rewards(user2.account_id()))
+        .unwrap_json::<Vec<(AccountId, U128)>>();
+    assert!(owed2.is_empty());
+}
+
+#[test]
+fn test_farm_claim_vests_over_time() {
+    let (root, pool) = setup();
+    let user1 = root.create_user(
+        AccountId::new_unchecked("user1".to_string()),
+        to_yocto("100000"),
+    );
+    assert_all_success(call!(
+        user1,
+        pool.deposit_and_stake(),
+        deposit = to_yocto("10000")
+    ));
+
+    let start_date = root.borrow_runtime().cur_block.block_timestamp + ONE_SEC_IN_NS;
+    let end_date = start_date + ONE_SEC_IN_NS;
+    // Each simulated block is one second and the deploy and claim calls
+    // below take several blocks, so the cliff is set well after the claim
+    // and each of the 4 periods lasts 10 seconds.
+    let cliff_date = end_date + ONE_SEC_IN_NS * 20;
+    let vesting_end_date = cliff_date + ONE_SEC_IN_NS * 40;
+    let msg = serde_json::to_string(&json!({
+        "name": "Retention",
+        "start_date": format!("{}", start_date),
+        "end_date": format!("{}", end_date),
+        "cliff_date": format!("{}", cliff_date),
+        "vesting_end_date": format!("{}", vesting_end_date),
+        "period_count": 4,
+    }))
+    .unwrap();
+    assert_all_success(root.call(
+        AccountId::new_unchecked(TOKEN_ACCOUNT_ID.to_string()),
+        "ft_transfer_call",
+        &serde_json::to_vec(&json!({ "receiver_id": STAKING_POOL_ACCOUNT_ID, "amount": to_yocto("1000").to_string(), "msg": msg })).unwrap(),
+        near_sdk_sim::DEFAULT_GAS,
+        1,
+    ));
+
+    // Move past the farm's end_date so the full amount has accrued.
+    for _ in 0..3 {
+        root.borrow_runtime_mut().produce_block().unwrap();
+    }
+    assert_all_success(call!(user1, pool.claim(0)));
+
+    // Claiming locks the reward into a vesting schedule instead of paying
+    // it out -- nothing is released before the cliff.
+    let (total, claimed) = view!(pool.get_vesting(user1.account_id(), 0))
+        .unwrap_json::<Option<(U128, U128)>>()
+        .unwrap();
+    assert!(total.0 > 0);
+    assert_eq!(claimed.0, 0);
+
+    // Move halfway through the 4-period vest and release what's due.
+    while root.borrow_runtime().cur_block.block_timestamp < cliff_date + (vesting_end_date - cliff_date) / 2 {
+        root.borrow_runtime_mut().produce_block().unwrap();
+    }
+    assert_all_success(call!(user1, pool.withdraw_vested(0)));
+    let (_total2, claimed2) = view!(pool.get_vesting(user1.account_id(), 0))
+        .unwrap_json::<Option<(U128, U128)>>()
+        .unwrap();
+    assert!(claimed2.0 > 0 && claimed2.0 < total.0);
+}
+
+#[test]
+fn test_multi_validator_split_matches_weights() {
+    let (root, pool) = setup();
+
+    let reward_ratio = Ratio {
+        numerator: 1,
+        denominator: 10,
+    };
+    let burn_ratio = Ratio {
+        numerator: 3,
+        denominator: 10,
+    };
+    let validator1 = deploy!(
+        contract: StakingContractContract,
+        contract_id: "validator1".to_string(),
+        bytes: &STAKING_FARM_BYTES,
+        signer_account: root,
+        deposit: to_yocto("5"),
+        init_method: new(root.account_id(), STAKING_KEY.parse().unwrap(), reward_ratio, burn_ratio)
+    );
+    let validator2 = deploy!(
+        contract: StakingContractContract,
+        contract_id: "validator2".to_string(),
+        bytes: &STAKING_FARM_BYTES,
+        signer_account: root,
+        deposit: to_yocto("5"),
+        init_method: new(root.account_id(), STAKING_KEY.parse().unwrap(), reward_ratio, burn_ratio)
+    );
+
+    // validator2 has twice validator1's weight, so it should end up with
+    // twice the delegated NEAR.
+    assert_all_success(call!(
+        root,
+        pool.add_validator(validator1.account_id(), 1)
+    ));
+    assert_all_success(call!(
+        root,
+        pool.add_validator(validator2.account_id(), 2)
+    ));
+
+    let user1 = root.create_user(
+        AccountId::new_unchecked("user1".to_string()),
+        to_yocto("100000"),
+    );
+    assert_all_success(call!(
+        user1,
+        pool.deposit_and_stake(),
+        deposit = to_yocto("9000")
+    ));
+    wait_epoch(&root);
+
+    let validators = view!(pool.get_validators()).unwrap_json::<Vec<(AccountId, u16, U128)>>();
+    let staked1 = validators.iter().find(|(id, ..)| id == &validator1.account_id()).unwrap().2 .0;
+    let staked2 = validators.iter().find(|(id, ..)| id == &validator2.account_id()).unwrap().2 .0;
+    assert_eq!(staked1, to_yocto("3000"));
+    assert_eq!(staked2, to_yocto("6000"));
 }
\ No newline at end of file